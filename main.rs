@@ -17,6 +17,10 @@ enum Type {
     List(Box<Type>),
     Void,
     Func(Vec<Type>, Box<Type>),
+    // Unknown stand-in handed out by the inference pass (see `tc`).
+    Var(u32),
+    // A user-declared algebraic data type, referenced by name.
+    Named(String),
 }
 
 // ===== Value (with Rc/RefCell for GC-like sharing) =====
@@ -27,6 +31,7 @@ enum Value {
     Bool(bool),
     Str(Rc<String>),
     List(Rc<RefCell<Vec<Value>>>),
+    Ctor(String, Vec<Value>),
 }
 
 impl Value {
@@ -45,6 +50,9 @@ impl Value {
                     Type::List(Box::new(Type::Void))
                 }
             }
+            // The value alone carries only its constructor name, not the ADT it
+            // belongs to; callers with a `Context` map it through `ctx.ctors`.
+            Value::Ctor(name,_) => Type::Named(name.clone()),
         }
     }
 }
@@ -64,6 +72,17 @@ impl fmt::Display for Value {
                               .join(", ");
                 write!(f, "[{}]", items)
             }
+            Value::Ctor(name, fields) => {
+                if fields.is_empty() {
+                    write!(f, "{}", name)
+                } else {
+                    let items = fields.iter()
+                                      .map(|v| v.to_string())
+                                      .collect::<Vec<_>>()
+                                      .join(", ");
+                    write!(f, "{}({})", name, items)
+                }
+            }
         }
     }
 }
@@ -73,19 +92,49 @@ impl fmt::Display for Value {
 enum Expr {
     Literal(Value),
     Var(String),
-    BinOp(Box<Expr>, char, Box<Expr>),
+    BinOp(Box<Expr>, Op, Box<Expr>),
+    Unary(UnOp, Box<Expr>),
     Call(String, Vec<Expr>),
+    Ctor(String, Vec<Expr>),
+}
+
+// Binary operators, roughly in order of increasing binding power.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Or, And,
+    Eq, Ne, Lt, Le, Gt, Ge,
+    Add, Sub,
+    Mul, Div,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum UnOp {
+    Neg,
+    Not,
+}
+
+// A pattern in a `match` arm.
+#[derive(Clone, Debug)]
+enum Pattern {
+    Wildcard,
+    Literal(Value),
+    Bind(String),
+    Ctor(String, Vec<Pattern>),
 }
 
 #[derive(Clone, Debug)]
 enum Stmt {
-    VarDef(String, Type, Expr),
+    VarDef(String, Option<Type>, Expr),
     Print(Vec<Expr>),
     Shell(Vec<String>),
     SetShell(String),
     If(Expr, Vec<Stmt>, Vec<Stmt>),
     While(Expr, Vec<Stmt>),
     FuncDef(String, Vec<(String,Type)>, Type, Vec<Stmt>),
+    // type Name = Ctor | Ctor(Int, Str) | …  — each ctor is (name, field types).
+    TypeDef(String, Vec<(String, Vec<Type>)>),
+    Match(Expr, Vec<(Pattern, Vec<Stmt>)>),
+    Return(Expr),
     Exit,
     Clear,
     Help,
@@ -97,6 +146,12 @@ struct Context {
     vars:  HashMap<String, Value>,
     types: HashMap<String, Type>,
     funcs: HashMap<String,(Vec<(String,Type)>,Type,Vec<Stmt>)>,
+    // Constructor name -> (field types, owning ADT name).
+    ctors: HashMap<String,(Vec<Type>,String)>,
+    // ADT name -> its constructor names (used for exhaustiveness warnings).
+    adts:  HashMap<String,Vec<String>>,
+    // Declared return type of the function body currently being checked.
+    ret:   Option<Type>,
     shell: String,
 }
 
@@ -106,6 +161,9 @@ impl Context {
             vars:  HashMap::new(),
             types: HashMap::new(),
             funcs: HashMap::new(),
+            ctors: HashMap::new(),
+            adts:  HashMap::new(),
+            ret:   None,
             shell: "/bin/sh".into(),
         }
     }
@@ -159,11 +217,12 @@ fn parse_stmt(lines: &[&str]) -> (Stmt, usize) {
     if let Some(rest) = line.strip_prefix("var ") {
         if let Some((left, expr)) = rest.split_once('=') {
             let left = left.trim();
+            let e = parse_expr(expr.trim());
+            // The annotation is now optional: `var x = 2 + 3` lets `tc` infer it.
             if let Some((name, tystr)) = left.split_once(':') {
-                let ty = parse_type(tystr.trim());
-                let e = parse_expr(expr.trim());
-                return (Stmt::VarDef(name.trim().into(), ty, e),1);
+                return (Stmt::VarDef(name.trim().into(), Some(parse_type(tystr.trim())), e),1);
             }
+            return (Stmt::VarDef(left.into(), None, e),1);
         }
     }
     // print(...)
@@ -173,7 +232,7 @@ fn parse_stmt(lines: &[&str]) -> (Stmt, usize) {
         } else {
             r.trim()
         };
-        let args = inside.split(',').map(|a| parse_expr(a.trim())).collect();
+        let args = parse_expr_list(inside);
         return (Stmt::Print(args),1);
     }
     // shell cmd...
@@ -189,14 +248,14 @@ fn parse_stmt(lines: &[&str]) -> (Stmt, usize) {
         while idx<lines.len() {
             let t=lines[idx].trim();
             if t=="else"||t=="end"{ break }
-            then_b.push(parse_stmt(&lines[idx..]).0); idx+=1;
+            let (s,cons)=parse_stmt(&lines[idx..]); then_b.push(s); idx+=cons;
         }
         if idx<lines.len() && lines[idx].trim()=="else" {
             idx+=1;
             while idx<lines.len() {
                 let t=lines[idx].trim();
                 if t=="end"{ break }
-                else_b.push(parse_stmt(&lines[idx..]).0); idx+=1;
+                let (s,cons)=parse_stmt(&lines[idx..]); else_b.push(s); idx+=cons;
             }
         }
         return (Stmt::If(cond,then_b,else_b), idx+1);
@@ -207,10 +266,55 @@ fn parse_stmt(lines: &[&str]) -> (Stmt, usize) {
         let mut body = Vec::new();
         let mut idx=1;
         while idx<lines.len() && lines[idx].trim()!="end" {
-            body.push(parse_stmt(&lines[idx..]).0); idx+=1;
+            let (s,cons)=parse_stmt(&lines[idx..]); body.push(s); idx+=cons;
         }
         return (Stmt::While(cond,body), idx+1);
     }
+    // type Name = Ctor1 | Ctor2(Int, Str) | …
+    if let Some(rest) = line.strip_prefix("type ") {
+        if let Some((name, body)) = rest.split_once('=') {
+            let mut ctors = Vec::new();
+            for alt in body.split('|').map(str::trim).filter(|a| !a.is_empty()) {
+                if let Some(idx) = alt.find('(') {
+                    let cname = alt[..idx].trim();
+                    let inner = alt[idx+1..alt.rfind(')').unwrap_or(alt.len())].trim();
+                    let fields = if inner.is_empty() {
+                        Vec::new()
+                    } else {
+                        inner.split(',').map(|t| parse_type(t.trim())).collect()
+                    };
+                    ctors.push((cname.into(), fields));
+                } else {
+                    ctors.push((alt.into(), Vec::new()));
+                }
+            }
+            return (Stmt::TypeDef(name.trim().into(), ctors),1);
+        }
+    }
+    // match expr with | Pat => stmt … | _ => … end
+    if let Some(rest) = line.strip_prefix("match ") {
+        if let Some(wpos) = rest.find(" with") {
+            let scrut = parse_expr(rest[..wpos].trim());
+            let mut arms = Vec::new();
+            let mut idx = 1;
+            while idx < lines.len() && lines[idx].trim() != "end" {
+                let arm = lines[idx].trim();
+                if let Some(body) = arm.strip_prefix('|') {
+                    if let Some((pat, stmt)) = body.split_once("=>") {
+                        let pattern = parse_pattern(pat.trim());
+                        let body_stmt = parse_stmt(&[stmt.trim()]).0;
+                        arms.push((pattern, vec![body_stmt]));
+                    }
+                }
+                idx += 1;
+            }
+            return (Stmt::Match(scrut, arms), idx+1);
+        }
+    }
+    // return expr
+    if let Some(r) = line.strip_prefix("return ") {
+        return (Stmt::Return(parse_expr(r.trim())),1);
+    }
     // fn name(p:Type,…)->Type … end
     if line.starts_with("fn ") {
         let after=&line[3..];
@@ -227,7 +331,7 @@ fn parse_stmt(lines: &[&str]) -> (Stmt, usize) {
         } else { Type::Void };
         let mut body=Vec::new(); let mut idx=1;
         while idx<lines.len() && lines[idx].trim()!="end" {
-            body.push(parse_stmt(&lines[idx..]).0); idx+=1;
+            let (s,cons)=parse_stmt(&lines[idx..]); body.push(s); idx+=cons;
         }
         return (Stmt::FuncDef(name.into(),params,ret_ty,body), idx+1);
     }
@@ -245,39 +349,477 @@ fn parse_type(s: &str) -> Type {
             let inner = &t[5..t.len()-1];
             Type::List(Box::new(parse_type(inner)))
         }
+        // A capitalised bare word names a user-declared ADT.
+        _ if s.chars().next().is_some_and(|c| c.is_uppercase()) => Type::Named(s.into()),
         _ => Type::Void,
     }
 }
 
+// Constructors are distinguished from variables/calls by a leading capital.
+fn is_ctor_name(s: &str) -> bool {
+    s.chars().next().is_some_and(|c| c.is_uppercase())
+}
+
+fn parse_pattern(s: &str) -> Pattern {
+    let s = s.trim();
+    if s == "_" { return Pattern::Wildcard; }
+    if s.starts_with('"') && s.ends_with('"') && s.len() >= 2 {
+        return Pattern::Literal(Value::Str(Rc::new(s[1..s.len()-1].into())));
+    }
+    if s == "true"  { return Pattern::Literal(Value::Bool(true)); }
+    if s == "false" { return Pattern::Literal(Value::Bool(false)); }
+    if let Ok(i) = s.parse::<i64>() { return Pattern::Literal(Value::Int(i)); }
+    if let Some(idx) = s.find('(') {
+        if s.ends_with(')') {
+            let name = &s[..idx];
+            let inner = &s[idx+1..s.len()-1];
+            let subs = if inner.trim().is_empty() {
+                Vec::new()
+            } else {
+                inner.split(',').map(parse_pattern).collect()
+            };
+            return Pattern::Ctor(name.into(), subs);
+        }
+    }
+    if is_ctor_name(s) { return Pattern::Ctor(s.into(), Vec::new()); }
+    Pattern::Bind(s.into())
+}
+
+// A single lexeme of the expression grammar.
+#[derive(Clone, Debug, PartialEq)]
+enum Tok {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bool(bool),
+    Ident(String),
+    Op(String),
+    LParen,
+    RParen,
+    Comma,
+}
+
+// Expression tokenizer: numbers, strings, identifiers, operators and parens.
+fn lex_expr(s: &str) -> Vec<Tok> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut toks = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ' ' | '\t' => { i += 1; }
+            '(' => { toks.push(Tok::LParen); i += 1; }
+            ')' => { toks.push(Tok::RParen); i += 1; }
+            ',' => { toks.push(Tok::Comma); i += 1; }
+            '"' => {
+                let mut buf = String::new();
+                i += 1;
+                while i < chars.len() && chars[i] != '"' { buf.push(chars[i]); i += 1; }
+                i += 1; // closing quote
+                toks.push(Tok::Str(buf));
+            }
+            c if c.is_ascii_digit() => {
+                let mut buf = String::new();
+                let mut is_float = false;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    if chars[i] == '.' { is_float = true; }
+                    buf.push(chars[i]); i += 1;
+                }
+                if is_float {
+                    toks.push(Tok::Float(buf.parse().unwrap_or(0.0)));
+                } else {
+                    toks.push(Tok::Int(buf.parse().unwrap_or(0)));
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut buf = String::new();
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    buf.push(chars[i]); i += 1;
+                }
+                match buf.as_str() {
+                    "true"  => toks.push(Tok::Bool(true)),
+                    "false" => toks.push(Tok::Bool(false)),
+                    _       => toks.push(Tok::Ident(buf)),
+                }
+            }
+            _ => {
+                // Operators, longest match first for the two-char forms.
+                let two: String = chars[i..(i+2).min(chars.len())].iter().collect();
+                if matches!(two.as_str(), "=="|"!="|"<="|">="|"&&"|"||") {
+                    toks.push(Tok::Op(two)); i += 2;
+                } else {
+                    toks.push(Tok::Op(c.to_string())); i += 1;
+                }
+            }
+        }
+    }
+    toks
+}
+
+// Left/right binding powers for an infix operator; `None` if not infix.
+fn infix_bp(op: &str) -> Option<(u8, u8)> {
+    let bp = match op {
+        "||" => 1,
+        "&&" => 2,
+        "==" | "!=" | "<" | "<=" | ">" | ">=" => 3,
+        "+" | "-" => 5,
+        "*" | "/" => 7,
+        _ => return None,
+    };
+    Some((bp, bp + 1)) // left-associative
+}
+
+fn to_op(lex: &str) -> Op {
+    match lex {
+        "||" => Op::Or,  "&&" => Op::And,
+        "==" => Op::Eq,  "!=" => Op::Ne,
+        "<"  => Op::Lt,  "<=" => Op::Le,
+        ">"  => Op::Gt,  ">=" => Op::Ge,
+        "+"  => Op::Add, "-"  => Op::Sub,
+        "*"  => Op::Mul, _    => Op::Div,
+    }
+}
+
+struct Parser {
+    toks: Vec<Tok>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Tok> { self.toks.get(self.pos) }
+    fn next(&mut self) -> Option<Tok> {
+        let t = self.toks.get(self.pos).cloned();
+        if t.is_some() { self.pos += 1; }
+        t
+    }
+
+    // Precedence-climbing: parse a prefix atom, then fold in infix operators
+    // whose left binding power exceeds `min_bp`.
+    fn expr(&mut self, min_bp: u8) -> Expr {
+        let mut left = self.nud();
+        while let Some(Tok::Op(op)) = self.peek() {
+            let op = op.clone();
+            match infix_bp(&op) {
+                Some((lbp, rbp)) if lbp > min_bp => {
+                    self.pos += 1;
+                    let right = self.expr(rbp);
+                    left = Expr::BinOp(Box::new(left), to_op(&op), Box::new(right));
+                }
+                _ => break,
+            }
+        }
+        left
+    }
+
+    // Prefix position: literals, parenthesised groups, unary operators, and
+    // identifiers (bare, calls, or constructors).
+    fn nud(&mut self) -> Expr {
+        match self.next() {
+            Some(Tok::Int(i))   => Expr::Literal(Value::Int(i)),
+            Some(Tok::Float(f)) => Expr::Literal(Value::Float(f)),
+            Some(Tok::Bool(b))  => Expr::Literal(Value::Bool(b)),
+            Some(Tok::Str(s))   => Expr::Literal(Value::Str(Rc::new(s))),
+            Some(Tok::LParen)   => {
+                let e = self.expr(0);
+                if self.peek() == Some(&Tok::RParen) { self.pos += 1; }
+                e
+            }
+            Some(Tok::Op(op)) if op == "-" => Expr::Unary(UnOp::Neg, Box::new(self.expr(9))),
+            Some(Tok::Op(op)) if op == "!" => Expr::Unary(UnOp::Not, Box::new(self.expr(9))),
+            Some(Tok::Ident(name)) => {
+                if self.peek() == Some(&Tok::LParen) {
+                    self.pos += 1;
+                    let args = self.arg_list();
+                    if is_ctor_name(&name) { Expr::Ctor(name, args) }
+                    else { Expr::Call(name, args) }
+                } else if is_ctor_name(&name) {
+                    Expr::Ctor(name, Vec::new())
+                } else {
+                    Expr::Var(name)
+                }
+            }
+            _ => Expr::Var(String::new()),
+        }
+    }
+
+    // Parse a comma-separated argument list up to and including the `)`.
+    fn arg_list(&mut self) -> Vec<Expr> {
+        let mut args = Vec::new();
+        if self.peek() == Some(&Tok::RParen) { self.pos += 1; return args; }
+        loop {
+            args.push(self.expr(0));
+            match self.next() {
+                Some(Tok::Comma) => continue,
+                _ => break, // RParen or end
+            }
+        }
+        args
+    }
+}
+
 fn parse_expr(s: &str) -> Expr {
-    let s=s.trim();
-    if s.starts_with('"') && s.ends_with('"') {
-        return Expr::Literal(Value::Str(Rc::new(s[1..s.len()-1].into())));
-    }
-    if s=="true"  { return Expr::Literal(Value::Bool(true)) }
-    if s=="false" { return Expr::Literal(Value::Bool(false)) }
-    if s.contains('.') {
-        if let Ok(f)=s.parse() { return Expr::Literal(Value::Float(f)) }
-    }
-    if let Ok(i)=s.parse() { return Expr::Literal(Value::Int(i)) }
-    if let Some(idx)=s.find('+') {
-        return Expr::BinOp(
-            Box::new(parse_expr(&s[..idx])),
-            '+',
-            Box::new(parse_expr(&s[idx+1..]))
-        );
-    }
-    if let Some(idx)=s.find('(') {
-        let name=&s[..idx];
-        let args=&s[idx+1..s.len()-1];
-        let vs= if args.is_empty() {
-            Vec::new()
-        } else {
-            args.split(',').map(|a| parse_expr(a.trim())).collect()
-        };
-        return Expr::Call(name.into(),vs);
+    let mut p = Parser { toks: lex_expr(s.trim()), pos: 0 };
+    p.expr(0)
+}
+
+// Parse a comma-separated list of top-level expressions (e.g. `print` args),
+// respecting nested parentheses.
+fn parse_expr_list(s: &str) -> Vec<Expr> {
+    let s = s.trim();
+    if s.is_empty() { return Vec::new(); }
+    let mut p = Parser { toks: lex_expr(s), pos: 0 };
+    let mut out = Vec::new();
+    loop {
+        out.push(p.expr(0));
+        match p.peek() {
+            Some(Tok::Comma) => { p.pos += 1; }
+            _ => break,
+        }
+    }
+    out
+}
+
+// ===== Type inference (Algorithm W) =====
+// A small Hindley-Milner pass: unknowns are `Type::Var(u32)`, resolved through a
+// mutable substitution. `infer_expr` walks an expression building a unified type,
+// and `generalize`/`instantiate` give `let`/`fn` bindings their polymorphic scheme.
+mod tc {
+    use super::{Context, Expr, Op, Type, UnOp, Value};
+    use std::collections::{HashMap, HashSet};
+
+    /// A type quantified over the variables in `vars`; instantiated on each use.
+    #[derive(Clone)]
+    pub struct Scheme {
+        pub vars: Vec<u32>,
+        pub ty: Type,
+    }
+
+    pub struct Infer {
+        subst: HashMap<u32, Type>,
+        counter: u32,
+    }
+
+    impl Infer {
+        pub fn new() -> Self {
+            Self { subst: HashMap::new(), counter: 0 }
+        }
+
+        fn fresh(&mut self) -> Type {
+            let id = self.counter;
+            self.counter += 1;
+            Type::Var(id)
+        }
+
+        /// Follow the substitution until reaching a concrete head or an unbound var.
+        pub fn resolve(&self, t: &Type) -> Type {
+            match t {
+                Type::Var(v) => match self.subst.get(v) {
+                    Some(inner) => self.resolve(inner),
+                    None => Type::Var(*v),
+                },
+                Type::List(e) => Type::List(Box::new(self.resolve(e))),
+                Type::Func(ps, r) => Type::Func(
+                    ps.iter().map(|p| self.resolve(p)).collect(),
+                    Box::new(self.resolve(r)),
+                ),
+                other => other.clone(),
+            }
+        }
+
+        fn occurs(&self, v: u32, t: &Type) -> bool {
+            match self.resolve(t) {
+                Type::Var(w) => v == w,
+                Type::List(e) => self.occurs(v, &e),
+                Type::Func(ps, r) => {
+                    ps.iter().any(|p| self.occurs(v, p)) || self.occurs(v, &r)
+                }
+                _ => false,
+            }
+        }
+
+        /// Make `a` and `b` equal, binding variables and recursing structurally.
+        pub fn unify(&mut self, a: &Type, b: &Type) -> Result<(), String> {
+            let a = self.resolve(a);
+            let b = self.resolve(b);
+            match (a, b) {
+                (Type::Var(v), Type::Var(w)) if v == w => Ok(()),
+                (Type::Var(v), other) | (other, Type::Var(v)) => {
+                    if self.occurs(v, &other) {
+                        return Err(format!("infinite type: t{} occurs in {:?}", v, other));
+                    }
+                    self.subst.insert(v, other);
+                    Ok(())
+                }
+                (Type::List(x), Type::List(y)) => self.unify(&x, &y),
+                (Type::Func(p1, r1), Type::Func(p2, r2)) => {
+                    if p1.len() != p2.len() {
+                        return Err(format!(
+                            "expected {:?}, got {:?}",
+                            Type::Func(p1, r1),
+                            Type::Func(p2, r2)
+                        ));
+                    }
+                    for (x, y) in p1.iter().zip(p2.iter()) {
+                        self.unify(x, y)?;
+                    }
+                    self.unify(&r1, &r2)
+                }
+                (x, y) if x == y => Ok(()),
+                (x, y) => Err(format!("expected {:?}, got {:?}", x, y)),
+            }
+        }
+
+        /// Replace each quantified variable of a scheme with a fresh unknown.
+        fn instantiate(&mut self, sc: &Scheme) -> Type {
+            let mut map = HashMap::new();
+            for v in &sc.vars {
+                if let Type::Var(id) = self.fresh() {
+                    map.insert(*v, id);
+                }
+            }
+            rename(&sc.ty, &map)
+        }
+
+        /// Quantify over variables free in `ty` but not in the surrounding env.
+        /// `except` drops a name from the env so looking up a binding can still
+        /// generalize over that binding's own type variables (let-polymorphism).
+        fn generalize(&self, ctx: &Context, ty: &Type, except: Option<&str>) -> Scheme {
+            let ty = self.resolve(ty);
+            let mut env_free = HashSet::new();
+            for (n, t) in &ctx.types {
+                if Some(n.as_str()) == except { continue; }
+                free_vars(&self.resolve(t), &mut env_free);
+            }
+            let mut free = HashSet::new();
+            free_vars(&ty, &mut free);
+            let vars = free.difference(&env_free).copied().collect();
+            Scheme { vars, ty }
+        }
+
+        pub fn infer_expr(&mut self, e: &Expr, ctx: &Context) -> Result<Type, String> {
+            match e {
+                Expr::Literal(v) => Ok(lit_type(v)),
+                Expr::Var(n) => {
+                    let t = ctx
+                        .types
+                        .get(n)
+                        .cloned()
+                        .ok_or_else(|| format!("Unknown var {}", n))?;
+                    // A bound variable is used at a fresh instance of its scheme;
+                    // exclude its own binding from the env so generalization fires.
+                    let sc = self.generalize(ctx, &t, Some(n));
+                    Ok(self.instantiate(&sc))
+                }
+                Expr::BinOp(a, op, b) => {
+                    let at = self.infer_expr(a, ctx)?;
+                    let bt = self.infer_expr(b, ctx)?;
+                    match op {
+                        Op::And | Op::Or => {
+                            self.unify(&at, &Type::Bool)?;
+                            self.unify(&bt, &Type::Bool)?;
+                            Ok(Type::Bool)
+                        }
+                        Op::Eq | Op::Ne => {
+                            self.unify(&at, &bt)?;
+                            Ok(Type::Bool)
+                        }
+                        Op::Lt | Op::Le | Op::Gt | Op::Ge => {
+                            self.unify(&at, &bt)?;
+                            // Ordering is only defined for numbers, matching the
+                            // evaluator; reject `Str`/`Bool`/etc comparisons.
+                            match self.resolve(&at) {
+                                Type::Int | Type::Float | Type::Var(_) => Ok(Type::Bool),
+                                other => Err(format!("ordering requires numeric operands, got {:?}", other)),
+                            }
+                        }
+                        _ => {
+                            self.unify(&at, &bt)?;
+                            Ok(self.resolve(&at))
+                        }
+                    }
+                }
+                Expr::Unary(op, e) => {
+                    let t = self.infer_expr(e, ctx)?;
+                    match op {
+                        UnOp::Neg => Ok(self.resolve(&t)),
+                        UnOp::Not => { self.unify(&t, &Type::Bool)?; Ok(Type::Bool) }
+                    }
+                }
+                Expr::Ctor(name, args) => {
+                    let (fields, parent) = ctx
+                        .ctors
+                        .get(name)
+                        .cloned()
+                        .ok_or_else(|| format!("Unknown constructor {}", name))?;
+                    if fields.len() != args.len() {
+                        return Err(format!(
+                            "Constructor {} expects {} field(s), got {}",
+                            name, fields.len(), args.len()
+                        ));
+                    }
+                    for (f, a) in fields.iter().zip(args.iter()) {
+                        let at = self.infer_expr(a, ctx)?;
+                        self.unify(f, &at)?;
+                    }
+                    Ok(Type::Named(parent))
+                }
+                Expr::Call(name, args) => {
+                    let (params, ret, _) = ctx
+                        .funcs
+                        .get(name)
+                        .cloned()
+                        .ok_or_else(|| format!("Unknown function {}", name))?;
+                    let sig = Type::Func(
+                        params.iter().map(|(_, t)| t.clone()).collect(),
+                        Box::new(ret),
+                    );
+                    // Instantiate the callee, then unify its parameters with the args.
+                    let sc = self.generalize(ctx, &sig, Some(name));
+                    let fresh = self.instantiate(&sc);
+                    let mut arg_tys = Vec::with_capacity(args.len());
+                    for a in args {
+                        arg_tys.push(self.infer_expr(a, ctx)?);
+                    }
+                    let result = self.fresh();
+                    self.unify(&fresh, &Type::Func(arg_tys, Box::new(result.clone())))?;
+                    Ok(self.resolve(&result))
+                }
+            }
+        }
+    }
+
+    fn lit_type(v: &Value) -> Type {
+        v.get_type()
+    }
+
+    /// Structurally rewrite the variables of a type through `map`.
+    fn rename(t: &Type, map: &HashMap<u32, u32>) -> Type {
+        match t {
+            Type::Var(v) => Type::Var(*map.get(v).unwrap_or(v)),
+            Type::List(e) => Type::List(Box::new(rename(e, map))),
+            Type::Func(ps, r) => Type::Func(
+                ps.iter().map(|p| rename(p, map)).collect(),
+                Box::new(rename(r, map)),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    fn free_vars(t: &Type, acc: &mut HashSet<u32>) {
+        match t {
+            Type::Var(v) => {
+                acc.insert(*v);
+            }
+            Type::List(e) => free_vars(e, acc),
+            Type::Func(ps, r) => {
+                for p in ps {
+                    free_vars(p, acc);
+                }
+                free_vars(r, acc);
+            }
+            _ => {}
+        }
     }
-    Expr::Var(s.into())
 }
 
 // ===== Type Checker =====
@@ -285,10 +827,14 @@ fn type_check_stmt(stmt: &Stmt, ctx: &mut Context) -> Result<(),String> {
     match stmt {
         Stmt::VarDef(name, ty, expr) => {
             let et=type_check_expr(expr,ctx)?;
-            if &et!=ty {
-                return Err(format!("Type mismatch for {}: expected {:?}, got {:?}", name, ty, et));
+            if let Some(ty)=ty {
+                if &et!=ty {
+                    return Err(format!("Type mismatch for {}: expected {:?}, got {:?}", name, ty, et));
+                }
             }
-            ctx.types.insert(name.clone(), ty.clone());
+            // With no annotation the inferred type stands in.
+            let resolved=ty.clone().unwrap_or(et);
+            ctx.types.insert(name.clone(), resolved);
             Ok(())
         }
         Stmt::Print(exprs) => {
@@ -314,60 +860,265 @@ fn type_check_stmt(stmt: &Stmt, ctx: &mut Context) -> Result<(),String> {
             Ok(())
         }
         Stmt::FuncDef(name,params,ret,body) => {
+            // Register the signature before checking the body so self-calls
+            // (recursion) resolve instead of hitting "Unknown function".
+            ctx.funcs.insert(name.clone(),(params.clone(),ret.clone(),body.clone()));
             let prev=ctx.types.clone();
+            let prev_ret=ctx.ret.take();
+            ctx.ret=Some(ret.clone());
             for (n,ty) in params { ctx.types.insert(n.clone(),ty.clone()); }
             for s in body { type_check_stmt(s,ctx)?; }
+            ctx.ret=prev_ret;
             ctx.types=prev;
-            ctx.funcs.insert(name.clone(),(params.clone(),ret.clone(),body.clone()));
+            Ok(())
+        }
+        Stmt::TypeDef(name,ctors) => {
+            for (cname,fields) in ctors {
+                ctx.ctors.insert(cname.clone(),(fields.clone(),name.clone()));
+            }
+            ctx.adts.insert(name.clone(), ctors.iter().map(|(c,_)| c.clone()).collect());
+            Ok(())
+        }
+        Stmt::Match(scrut,arms) => {
+            let st=type_check_expr(scrut,ctx)?;
+            let mut covered: Vec<String>=Vec::new();
+            let mut has_wildcard=false;
+            for (pat,body) in arms {
+                check_pattern(pat,&st,ctx)?;
+                match pat {
+                    Pattern::Ctor(c,_) => covered.push(c.clone()),
+                    Pattern::Wildcard | Pattern::Bind(_) => has_wildcard=true,
+                    _ => {}
+                }
+                // Bindings introduced by the pattern are visible in the body.
+                let prev=ctx.types.clone();
+                bind_pattern_types(pat,&st,ctx);
+                for s in body { type_check_stmt(s,ctx)?; }
+                ctx.types=prev;
+            }
+            // Warn (don't fail) when a sum type isn't fully covered.
+            if !has_wildcard {
+                if let Type::Named(tn)=&st {
+                    if let Some(all)=ctx.adts.get(tn) {
+                        let missing: Vec<_>=all.iter().filter(|c| !covered.contains(c)).collect();
+                        if !missing.is_empty() {
+                            eprintln!("Warning: match on {} does not cover {:?}", tn, missing);
+                        }
+                    }
+                }
+            }
+            Ok(())
+        }
+        Stmt::Return(e) => {
+            let et=type_check_expr(e,ctx)?;
+            // A `return` must agree with the enclosing function's declared type.
+            if let Some(ret)=ctx.ret.clone() {
+                if et!=ret {
+                    return Err(format!("Return type mismatch: expected {:?}, got {:?}", ret, et));
+                }
+            }
             Ok(())
         }
         Stmt::ExprStmt(e) => { let _=type_check_expr(e,ctx)?; Ok(()) }
     }
 }
 
+// Thin wrapper over the inference pass: run Algorithm W for a single expression
+// and return its fully-resolved type. Calls and un-annotated bindings now check.
 fn type_check_expr(expr: &Expr, ctx: &Context) -> Result<Type,String> {
-    match expr {
-        Expr::Literal(v) => Ok(v.get_type()),
-        Expr::Var(n) => ctx.types.get(n)
-            .cloned()
-            .ok_or(format!("Unknown var {}",n)),
-        Expr::BinOp(a,op,b) => {
-            let at=type_check_expr(a,ctx)?;
-            let bt=type_check_expr(b,ctx)?;
-            if at!=bt { return Err("Type mismatch in binop".into()) }
-            Ok(at)
+    let mut inf = tc::Infer::new();
+    let t = inf.infer_expr(expr, ctx)?;
+    Ok(inf.resolve(&t))
+}
+
+// Verify a pattern is consistent with the scrutinee's type.
+fn check_pattern(pat: &Pattern, ty: &Type, ctx: &Context) -> Result<(),String> {
+    match pat {
+        Pattern::Wildcard | Pattern::Bind(_) => Ok(()),
+        Pattern::Literal(v) => {
+            if ty!=&v.get_type() && !matches!(ty,Type::Var(_)) {
+                return Err(format!("Pattern literal {:?} does not match {:?}", v.get_type(), ty));
+            }
+            Ok(())
+        }
+        Pattern::Ctor(c,subs) => {
+            let (fields,parent)=ctx.ctors.get(c)
+                .cloned()
+                .ok_or_else(|| format!("Unknown constructor {}", c))?;
+            if let Type::Named(tn)=ty {
+                if tn!=&parent {
+                    return Err(format!("Constructor {} is not a {}", c, tn));
+                }
+            }
+            if subs.len()!=fields.len() {
+                return Err(format!("Constructor {} expects {} field(s), got {}", c, fields.len(), subs.len()));
+            }
+            for (s,f) in subs.iter().zip(fields.iter()) {
+                check_pattern(s,f,ctx)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+// Register the types of variables a pattern binds, for the arm's body.
+fn bind_pattern_types(pat: &Pattern, ty: &Type, ctx: &mut Context) {
+    match pat {
+        Pattern::Bind(name) => { ctx.types.insert(name.clone(), ty.clone()); }
+        Pattern::Ctor(c,subs) => {
+            if let Some((fields,_))=ctx.ctors.get(c).cloned() {
+                for (s,f) in subs.iter().zip(fields.iter()) {
+                    bind_pattern_types(s,f,ctx);
+                }
+            }
         }
-        Expr::Call(_,_) => Err("Function calls not yet typed".into()),
+        _ => {}
     }
 }
 
 // ===== Evaluator =====
+// How a statement hands control back to its caller. `Return` carries the value
+// up out of a function body; `Exit` unwinds the whole program, except that an
+// `exit` inside a called function only ends that call.
+enum Flow {
+    Normal,
+    Return(Value),
+    Exit,
+}
+
 fn eval_expr(e: &Expr, ctx: &mut Context) -> Option<Value> {
     match e {
         Expr::Literal(v) => Some(v.clone()),
         Expr::Var(n)     => ctx.vars.get(n).cloned(),
         Expr::BinOp(a,op,b) => {
+            // `&&`/`||` short-circuit on the left operand.
+            match op {
+                Op::And => {
+                    return match eval_expr(a,ctx)? {
+                        Value::Bool(false) => Some(Value::Bool(false)),
+                        Value::Bool(true)  => eval_expr(b,ctx),
+                        _ => None,
+                    };
+                }
+                Op::Or => {
+                    return match eval_expr(a,ctx)? {
+                        Value::Bool(true)  => Some(Value::Bool(true)),
+                        Value::Bool(false) => eval_expr(b,ctx),
+                        _ => None,
+                    };
+                }
+                _ => {}
+            }
             let x=eval_expr(a,ctx)?; let y=eval_expr(b,ctx)?;
             Some(match (x,y,*op) {
-                (Value::Int(p),Value::Int(q),'+')   => Value::Int(p+q),
-                (Value::Int(p),Value::Int(q),'-')   => Value::Int(p-q),
-                (Value::Int(p),Value::Int(q),'*')   => Value::Int(p*q),
-                (Value::Int(p),Value::Int(q),'/')   => Value::Int(p/q),
-                (Value::Float(p),Value::Float(q),'+') => Value::Float(p+q),
-                (Value::Float(p),Value::Float(q),'-') => Value::Float(p-q),
-                (Value::Float(p),Value::Float(q),'*') => Value::Float(p*q),
-                (Value::Float(p),Value::Float(q),'/') => Value::Float(p/q),
+                (Value::Int(p),Value::Int(q),Op::Add) => Value::Int(p+q),
+                (Value::Int(p),Value::Int(q),Op::Sub) => Value::Int(p-q),
+                (Value::Int(p),Value::Int(q),Op::Mul) => Value::Int(p*q),
+                (Value::Int(p),Value::Int(q),Op::Div) => Value::Int(p/q),
+                (Value::Float(p),Value::Float(q),Op::Add) => Value::Float(p+q),
+                (Value::Float(p),Value::Float(q),Op::Sub) => Value::Float(p-q),
+                (Value::Float(p),Value::Float(q),Op::Mul) => Value::Float(p*q),
+                (Value::Float(p),Value::Float(q),Op::Div) => Value::Float(p/q),
+                (Value::Int(p),Value::Int(q),_)     => cmp_op(*op, p.partial_cmp(&q)),
+                (Value::Float(p),Value::Float(q),_) => cmp_op(*op, p.partial_cmp(&q)),
+                (x,y,Op::Eq) => Value::Bool(values_eq(&x,&y)),
+                (x,y,Op::Ne) => Value::Bool(!values_eq(&x,&y)),
                 _ => return None,
             })
         }
-        Expr::Call(_,_) => None, // future: call user funcs
+        Expr::Unary(op,e) => {
+            let v=eval_expr(e,ctx)?;
+            Some(match (op,v) {
+                (UnOp::Neg, Value::Int(i))   => Value::Int(-i),
+                (UnOp::Neg, Value::Float(f)) => Value::Float(-f),
+                (UnOp::Not, Value::Bool(b))  => Value::Bool(!b),
+                _ => return None,
+            })
+        }
+        Expr::Call(name, args) => {
+            // Evaluate arguments in the caller's frame first.
+            let mut argv = Vec::with_capacity(args.len());
+            for a in args { argv.push(eval_expr(a,ctx)?); }
+            let (params,_ret,body) = ctx.funcs.get(name)?.clone();
+            // Push a new frame: clone the outer vars so globals stay visible, then
+            // bind parameters (shadowing), and restore the frame on the way out.
+            let saved = std::mem::take(&mut ctx.vars);
+            let saved_types = ctx.types.clone();
+            ctx.vars = saved.clone();
+            for ((pn,ty),v) in params.iter().zip(argv) {
+                ctx.vars.insert(pn.clone(), v);
+                // Keep the type environment in step so the per-statement
+                // re-check inside `exec_stmt` can see the parameters too.
+                ctx.types.insert(pn.clone(), ty.clone());
+            }
+            let mut result = None;
+            for s in &body {
+                match exec_stmt(s,ctx) {
+                    Flow::Return(v) => { result = Some(v); break; }
+                    Flow::Exit => break,
+                    Flow::Normal => {}
+                }
+            }
+            ctx.vars = saved;
+            ctx.types = saved_types;
+            result
+        }
+        Expr::Ctor(name, args) => {
+            let mut vals = Vec::with_capacity(args.len());
+            for a in args { vals.push(eval_expr(a,ctx)?); }
+            Some(Value::Ctor(name.clone(), vals))
+        }
+    }
+}
+
+// Try to match `value` against `pat`, collecting any bindings. Returns false
+// without touching `binds` partially-committed if the match fails.
+fn match_pattern(pat: &Pattern, value: &Value, binds: &mut Vec<(String,Value)>) -> bool {
+    match pat {
+        Pattern::Wildcard => true,
+        Pattern::Bind(name) => { binds.push((name.clone(), value.clone())); true }
+        Pattern::Literal(lit) => values_eq(lit, value),
+        Pattern::Ctor(name, subs) => match value {
+            Value::Ctor(vname, fields) if vname==name && fields.len()==subs.len() => {
+                let mark = binds.len();
+                let ok = subs.iter().zip(fields.iter()).all(|(s,f)| match_pattern(s,f,binds));
+                if !ok { binds.truncate(mark); }
+                ok
+            }
+            _ => false,
+        },
     }
 }
 
-fn exec_stmt(stmt: &Stmt, ctx: &mut Context) -> bool {
+// Turn an ordering into the boolean result of a comparison operator.
+fn cmp_op(op: Op, ord: Option<std::cmp::Ordering>) -> Value {
+    use std::cmp::Ordering::*;
+    let Some(ord) = ord else { return Value::Bool(false); };
+    Value::Bool(match op {
+        Op::Eq => ord == Equal,
+        Op::Ne => ord != Equal,
+        Op::Lt => ord == Less,
+        Op::Le => ord != Greater,
+        Op::Gt => ord == Greater,
+        Op::Ge => ord != Less,
+        _ => false,
+    })
+}
+
+fn values_eq(a: &Value, b: &Value) -> bool {
+    match (a,b) {
+        (Value::Int(x),Value::Int(y))     => x==y,
+        (Value::Float(x),Value::Float(y)) => x==y,
+        (Value::Bool(x),Value::Bool(y))   => x==y,
+        (Value::Str(x),Value::Str(y))     => x==y,
+        _ => false,
+    }
+}
+
+fn exec_stmt(stmt: &Stmt, ctx: &mut Context) -> Flow {
     if let Err(e) = type_check_stmt(stmt,ctx) {
         eprintln!("Type error: {}",e);
-        return false;
+        return Flow::Normal;
     }
     match stmt {
         Stmt::VarDef(n,_,e) => {
@@ -375,32 +1126,83 @@ fn exec_stmt(stmt: &Stmt, ctx: &mut Context) -> bool {
         }
         Stmt::Print(es) => {
             let out=es.iter()
-                .map(|e| eval_expr(e,ctx).unwrap().to_string())
+                // A void call (no `return`) yields no value; degrade to empty
+                // rather than panicking on `.unwrap()`.
+                .map(|e| eval_expr(e,ctx).map(|v| v.to_string()).unwrap_or_default())
                 .collect::<Vec<_>>()
                 .join(" ");
             println!("{}", out);
         }
         Stmt::Shell(cmds) => {
-            if cmds.is_empty() { return false; }
+            if cmds.is_empty() { return Flow::Normal; }
             let mut c = Command::new(&cmds[0]);
             for arg in &cmds[1..] { c.arg(arg); }
             let _=c.status();
         }
         Stmt::SetShell(sh) => ctx.shell=sh.clone(),
         Stmt::If(cond,t,e) => {
-            if matches!(eval_expr(cond,ctx),Some(Value::Bool(true))) {
-                for s in t { if exec_stmt(s,ctx) { return true; } }
-            } else {
-                for s in e { if exec_stmt(s,ctx) { return true; } }
+            let branch = if matches!(eval_expr(cond,ctx),Some(Value::Bool(true))) { t } else { e };
+            for s in branch {
+                match exec_stmt(s,ctx) {
+                    Flow::Normal => {}
+                    other => return other,
+                }
             }
         }
         Stmt::While(cond,body) => {
             while matches!(eval_expr(cond,ctx),Some(Value::Bool(true))) {
-                for s in body { if exec_stmt(s,ctx) { return true; } }
+                for s in body {
+                    match exec_stmt(s,ctx) {
+                        Flow::Normal => {}
+                        other => return other,
+                    }
+                }
             }
         }
         Stmt::FuncDef(_,_,_,_) => { /* stored in type_check */ }
-        Stmt::Exit => return true,
+        Stmt::TypeDef(_,_) => { /* registered in type_check */ }
+        Stmt::Match(scrut,arms) => {
+            if let Some(v)=eval_expr(scrut,ctx) {
+                for (pat,body) in arms {
+                    let mut binds=Vec::new();
+                    if match_pattern(pat,&v,&mut binds) {
+                        // Bind the matched fields into a fresh frame for this arm.
+                        let saved=ctx.vars.clone();
+                        let saved_types=ctx.types.clone();
+                        for (n,val) in binds {
+                            // Mirror the binding into the type env so the per-statement
+                            // re-check inside `exec_stmt` can see it.
+                            let ty = match &val {
+                                Value::Ctor(c,_) => ctx.ctors.get(c)
+                                    .map(|(_,parent)| Type::Named(parent.clone()))
+                                    .unwrap_or_else(|| val.get_type()),
+                                _ => val.get_type(),
+                            };
+                            ctx.types.insert(n.clone(), ty);
+                            ctx.vars.insert(n,val);
+                        }
+                        let mut flow=Flow::Normal;
+                        for s in body {
+                            match exec_stmt(s,ctx) {
+                                Flow::Normal => {}
+                                other => { flow=other; break; }
+                            }
+                        }
+                        ctx.vars=saved;
+                        ctx.types=saved_types;
+                        if !matches!(flow,Flow::Normal) { return flow; }
+                        break;
+                    }
+                }
+            }
+        }
+        Stmt::Return(e) => {
+            return match eval_expr(e,ctx) {
+                Some(v) => Flow::Return(v),
+                None => Flow::Normal,
+            };
+        }
+        Stmt::Exit => return Flow::Exit,
         Stmt::Clear => { print!("\x1B[2J\x1B[1;1H"); io::stdout().flush().unwrap(); }
         Stmt::Help => {
             println!(r"help:
@@ -411,32 +1213,167 @@ fn exec_stmt(stmt: &Stmt, ctx: &mut Context) -> bool {
  if…then…else…end
  while…do…end
  fn name(p:Type,…)->Type…end
+ return expr
  exit, clear, help");
         }
         Stmt::ExprStmt(e) => { let _=eval_expr(e,ctx); }
     }
-    false
+    Flow::Normal
+}
+
+// Which compilation phase to stop at and dump, instead of executing.
+#[derive(Clone, Copy)]
+enum Stage {
+    Tokens,
+    Ast,
+    Types,
+    Run,
+}
+
+// Render a (possibly inferred) type in a readable surface form.
+fn show_type(t: &Type) -> String {
+    match t {
+        Type::Int   => "Int".into(),
+        Type::Float => "Float".into(),
+        Type::Bool  => "Bool".into(),
+        Type::Str   => "Str".into(),
+        Type::Void  => "Void".into(),
+        Type::List(e) => format!("List<{}>", show_type(e)),
+        Type::Func(ps,r) => {
+            let params = ps.iter().map(show_type).collect::<Vec<_>>().join(", ");
+            format!("({}) -> {}", params, show_type(r))
+        }
+        Type::Var(n) => format!("t{}", n),
+        Type::Named(n) => n.clone(),
+    }
+}
+
+fn dump_tokens(src: &str) {
+    for line in src.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+    {
+        println!("{:?}", lex_expr(line));
+    }
+}
+
+fn dump_ast(src: &str) {
+    for stmt in &parse_program(src) {
+        println!("{:#?}", stmt);
+    }
 }
 
-fn run_script(path: &str, ctx: &mut Context) {
-    match fs::read_to_string(path) {
-        Ok(src) => for stmt in &parse_program(&src) {
-            if exec_stmt(stmt,ctx) { break; }
+fn dump_types(src: &str, ctx: &mut Context) {
+    for stmt in &parse_program(src) {
+        if let Err(e) = type_check_stmt(stmt, ctx) {
+            println!("type error: {}", e);
+            continue;
+        }
+        match stmt {
+            Stmt::VarDef(n,_,e) => {
+                if let Ok(t) = type_check_expr(e, ctx) {
+                    println!("{} : {}", n, show_type(&t));
+                }
+            }
+            Stmt::ExprStmt(e) => {
+                if let Ok(t) = type_check_expr(e, ctx) {
+                    println!("{}", show_type(&t));
+                }
+            }
+            Stmt::FuncDef(name,params,ret,_) => {
+                let ps: Vec<Type> = params.iter().map(|(_,t)| t.clone()).collect();
+                println!("{} : {}", name, show_type(&Type::Func(ps, Box::new(ret.clone()))));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn run_script(path: &str, ctx: &mut Context, stage: Stage) {
+    let src = match fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(_) => { eprintln!("Cannot open file: {}",path); return; }
+    };
+    match stage {
+        Stage::Tokens => dump_tokens(&src),
+        Stage::Ast    => dump_ast(&src),
+        Stage::Types  => dump_types(&src, ctx),
+        Stage::Run    => for stmt in &parse_program(&src) {
+            if matches!(exec_stmt(stmt,ctx), Flow::Exit) { break; }
         },
-        Err(_) => eprintln!("Cannot open file: {}",path),
     }
 }
 
+// Does this line open a multi-line block construct?
+fn opens_block(line: &str) -> bool {
+    line.starts_with("if ") || line.starts_with("while ")
+        || line.starts_with("fn ") || line.starts_with("match ")
+}
+
 fn repl(ctx: &mut Context) {
     let stdin = io::stdin();
+    let mut buffer: Vec<String> = Vec::new();
+    let mut depth = 0usize;
     loop {
-        print!("dolphin> "); io::stdout().flush().unwrap();
+        let prompt = if depth > 0 { ".....> " } else { "dolphin> " };
+        print!("{}", prompt); io::stdout().flush().unwrap();
         let mut line=String::new();
         if stdin.read_line(&mut line).is_err() { break; }
-        for part in line.trim().split(';') {
-            if part.trim().is_empty() { continue; }
-            let (stmt,_) = parse_stmt(&[part.trim()]);
-            if exec_stmt(&stmt,ctx) { return; }
+        let trimmed = line.trim();
+
+        // Phase-inspection directives: dump a single phase instead of running.
+        if depth == 0 && buffer.is_empty() {
+            if let Some(rest) = trimmed.strip_prefix(":tokens ") {
+                println!("{:?}", lex_expr(rest.trim()));
+                continue;
+            }
+            if let Some(rest) = trimmed.strip_prefix(":ast ") {
+                println!("{:#?}", parse_stmt(&[rest.trim()]).0);
+                continue;
+            }
+            if let Some(rest) = trimmed.strip_prefix(":types ") {
+                let stmt = parse_stmt(&[rest.trim()]).0;
+                match type_check_stmt(&stmt, ctx) {
+                    Ok(()) => match &stmt {
+                        Stmt::ExprStmt(e) | Stmt::VarDef(_,_,e) => match type_check_expr(e, ctx) {
+                            Ok(t)  => println!("{}", show_type(&t)),
+                            Err(e) => println!("type error: {}", e),
+                        },
+                        _ => println!("ok"),
+                    },
+                    Err(e) => println!("type error: {}", e),
+                }
+                continue;
+            }
+        }
+
+        // At the top level, a non-block line runs immediately (keeping the old
+        // `;`-separated behaviour); a block opener starts accumulating.
+        if depth == 0 && buffer.is_empty() && !opens_block(trimmed) {
+            if trimmed.is_empty() { continue; }
+            for part in trimmed.split(';') {
+                if part.trim().is_empty() { continue; }
+                let (stmt,_) = parse_stmt(&[part.trim()]);
+                if matches!(exec_stmt(&stmt,ctx), Flow::Exit) { return; }
+            }
+            continue;
+        }
+
+        // Inside (or entering) a block: track nesting depth. Each opener adds a
+        // level, each `end` closes one; a lone `else` stays at the same depth.
+        buffer.push(trimmed.to_string());
+        if opens_block(trimmed) {
+            depth += 1;
+        } else if trimmed == "end" {
+            depth = depth.saturating_sub(1);
+        }
+
+        if depth == 0 {
+            let src = buffer.join("\n");
+            buffer.clear();
+            for stmt in &parse_program(&src) {
+                if matches!(exec_stmt(stmt,ctx), Flow::Exit) { return; }
+            }
         }
     }
 }
@@ -444,9 +1381,22 @@ fn repl(ctx: &mut Context) {
 fn main() {
     let mut ctx = Context::new();
     let args: Vec<String> = env::args().collect();
-    if args.len()==2 {
-        run_script(&args[1],&mut ctx);
-    } else {
-        repl(&mut ctx);
+    let mut stage = Stage::Run;
+    let mut path: Option<String> = None;
+    for a in &args[1..] {
+        if let Some(d) = a.strip_prefix("--dump=") {
+            stage = match d {
+                "tokens" => Stage::Tokens,
+                "ast"    => Stage::Ast,
+                "types"  => Stage::Types,
+                _ => { eprintln!("Unknown dump stage: {}", d); return; }
+            };
+        } else {
+            path = Some(a.clone());
+        }
+    }
+    match path {
+        Some(p) => run_script(&p, &mut ctx, stage),
+        None    => repl(&mut ctx),
     }
 }
\ No newline at end of file